@@ -1,9 +1,23 @@
-use std::{
+// `no_std` for real builds; the test harness still needs the `std` prelude
+#![cfg_attr(all(feature = "no_std", not(test)), no_std)]
+
+// the single-threaded core only needs `core`; the sync sibling pulls in `std` explicitly
+#[cfg(feature = "sync")]
+extern crate std;
+
+use core::{
     cell::{Cell, UnsafeCell},
     fmt,
     mem::MaybeUninit,
+    ops::Deref,
 };
 
+#[cfg(feature = "sync")]
+pub mod sync;
+
+#[cfg(feature = "no_std")]
+pub mod race;
+
 /// A `Lazy<T>` is a single-threaded lazy initialised container.
 ///
 /// It can be initialised by calling `get_or_create` with a function which will provide the
@@ -31,8 +45,8 @@ impl<T> Lazy<T> {
     pub fn into_inner(self) -> Option<T> {
         if self.init.get() {
             // SAFETY (initialisation): we've just checked self.init
-            // SAFETY (mutability): r/o operations only here
-            unsafe { Some(self.minit().assume_init_read()) }
+            // SAFETY (mutability): we own self
+            unsafe { Some((*self.t.get()).assume_init_read()) }
         } else {
             None
         }
@@ -42,16 +56,12 @@ impl<T> Lazy<T> {
         if self.init.get() {
             // SAFETY (initialisation): we've just checked self.init
             // SAFETY (mutability): r/o operations only here
-            unsafe { Some(self.minit().assume_init_ref()) }
+            unsafe { Some((*self.t.get()).assume_init_ref()) }
         } else {
             None
         }
     }
 
-    unsafe fn minit(&self) -> &mut MaybeUninit<T> {
-        &mut *self.t.get()
-    }
-
     pub fn get_or_create<F>(&self, f: F) -> &T
     where
         F: FnOnce() -> T,
@@ -69,13 +79,97 @@ impl<T> Lazy<T> {
             //                      and other shared references can't see the change
             //                      because we're single threaded
             unsafe {
-                self.minit().write(t);
+                (*self.t.get()).write(t);
             }
 
             self.init.set(true);
         }
 
-        unsafe { self.minit().assume_init_ref() }
+        // SAFETY (initialisation): self.init is now set, either just above or on entry
+        unsafe { (*self.t.get()).assume_init_ref() }
+    }
+
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if !self.init.get() {
+            // on Err we return early, leaving the cell uninitialised for a later retry
+            let t = f()?;
+
+            if self.init.get() {
+                // f() modified self
+                panic!("recursive modification of Lazy<T>");
+            }
+
+            // SAFETY (initialisation): we're uninitialised from the self.init check
+            // SAFETY (mutability): no possibility of other mutable references (&self)
+            //                      and other shared references can't see the change
+            //                      because we're single threaded
+            unsafe {
+                (*self.t.get()).write(t);
+            }
+
+            self.init.set(true);
+        }
+
+        // SAFETY (initialisation): self.init is now set, either just above or on entry
+        Ok(unsafe { (*self.t.get()).assume_init_ref() })
+    }
+
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.init.get() {
+            return Err(value);
+        }
+
+        // SAFETY (initialisation): we're uninitialised from the self.init check
+        // SAFETY (mutability): no possibility of other mutable references (&self)
+        //                      and other shared references can't see the change
+        //                      because we're single threaded
+        unsafe {
+            (*self.t.get()).write(value);
+        }
+
+        self.init.set(true);
+        Ok(())
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.init.get() {
+            // SAFETY (initialisation): we've just checked self.init
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { Some((*self.t.get()).assume_init_mut()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn take(&mut self) -> Option<T> {
+        if self.init.get() {
+            self.init.set(false);
+            // SAFETY (initialisation): we checked self.init, and we've just cleared it so
+            //                          the cell won't be read again until re-initialised
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { Some((*self.t.get()).assume_init_read()) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Lazy<T> {
+    fn drop(&mut self) {
+        if self.init.get() {
+            // SAFETY (initialisation): self.init tells us the value is live
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { (*self.t.get()).assume_init_drop() }
+        }
     }
 }
 
@@ -100,6 +194,185 @@ impl<T: Clone> Clone for Lazy<T> {
     }
 }
 
+/// A `LazyCell<T, F>` owns its initialiser, so it can be used as a drop-in lazy value.
+///
+/// Unlike [`Lazy<T>`], the closure is stored inside the cell rather than threaded through
+/// each access, mirroring `std::cell::LazyCell`. The first deref (or [`force`]) runs it.
+///
+/// [`force`]: LazyCell::force
+pub struct LazyCell<T, F = fn() -> T> {
+    // SAFETY (racing): we're !Sync so only a single thread can do this at a time
+    t: UnsafeCell<MaybeUninit<T>>,
+    f: Cell<Option<F>>,
+    init: Cell<bool>, // also prevents Sync + Send
+}
+
+impl<T, F> LazyCell<T, F> {
+    pub const fn new(f: F) -> Self {
+        Self {
+            t: UnsafeCell::new(MaybeUninit::uninit()),
+            f: Cell::new(Some(f)),
+            init: Cell::new(false),
+        }
+    }
+
+    pub fn into_inner(self) -> Result<T, F> {
+        if self.init.get() {
+            let this = core::mem::ManuallyDrop::new(self);
+            // SAFETY (initialisation): we've just checked self.init
+            // SAFETY (mutability): we own self, nothing else can observe the read
+            Ok(unsafe { (*this.t.get()).assume_init_read() })
+        } else {
+            // uninitialised, so the closure is still held unless an earlier `force` panicked
+            // while running it; in that case neither is left and this unwrap panics
+            Err(self.f.take().unwrap())
+        }
+    }
+}
+
+impl<T, F> LazyCell<T, F>
+where
+    F: FnOnce() -> T,
+{
+    pub fn force(&self) -> &T {
+        if !self.init.get() {
+            // taking the closure doubles as our reentrancy guard: a nested force sees None
+            let f = self
+                .f
+                .take()
+                .expect("recursive initialisation of LazyCell");
+            let t = f();
+
+            // SAFETY (initialisation): we're uninitialised from the self.init check
+            // SAFETY (mutability): no possibility of other mutable references (&self)
+            //                      and other shared references can't see the change
+            //                      because we're single threaded
+            unsafe {
+                (*self.t.get()).write(t);
+            }
+
+            self.init.set(true);
+        }
+
+        // SAFETY (initialisation): self.init is now set, either just above or on entry
+        unsafe { (*self.t.get()).assume_init_ref() }
+    }
+}
+
+impl<T, F> Deref for LazyCell<T, F>
+where
+    F: FnOnce() -> T,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.force()
+    }
+}
+
+impl<T, F> Drop for LazyCell<T, F> {
+    fn drop(&mut self) {
+        if self.init.get() {
+            // SAFETY (initialisation): self.init tells us the value is live
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { (*self.t.get()).assume_init_drop() }
+        }
+        // otherwise the closure (if any) is dropped with self.f
+    }
+}
+
+impl<T, F> fmt::Debug for LazyCell<T, F>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.init.get() {
+            // SAFETY (initialisation): self.init
+            Some(unsafe { (*self.t.get()).assume_init_ref() })
+        } else {
+            None
+        };
+        write!(f, "LazyCell({state:?})")
+    }
+}
+
+/// A `LazyTransform<T, U>` holds a seed of type `T` and, on first access, converts it once
+/// into a derived value of type `U`.
+///
+/// This is the single-threaded analogue of `try-lazy-init`'s `LazyTransform`: cheap to hold a
+/// descriptor (a path, a handle), expensive to realise it (open and parse the file).
+pub struct LazyTransform<T, U> {
+    // SAFETY (racing): we're !Sync so only a single thread can do this at a time
+    contents: UnsafeCell<Contents<T, U>>,
+    init: Cell<bool>, // also prevents Sync + Send
+}
+
+enum Contents<T, U> {
+    This(T),
+    That(U),
+    /// Transient state while `get_or_create` has moved the seed out and is running `f`. Leaving
+    /// this behind means a panicking or re-entrant `f` can't cause the seed to be dropped twice.
+    Gone,
+}
+
+impl<T, U> LazyTransform<T, U> {
+    pub const fn new(seed: T) -> Self {
+        Self {
+            contents: UnsafeCell::new(Contents::This(seed)),
+            init: Cell::new(false),
+        }
+    }
+
+    pub fn get_or_create<F>(&self, f: F) -> &U
+    where
+        F: FnOnce(T) -> U,
+    {
+        if !self.init.get() {
+            // Swap in `Gone` before running `f`, so the moved-out seed is never left live in the
+            // cell: if `f` panics the cell drops a `Gone` (a no-op) rather than the seed a second
+            // time. A re-entrant call sees `Gone` (or `That`) and panics instead of moving again.
+            // SAFETY (mutability): single threaded, &self, no other live references
+            let t = match core::mem::replace(unsafe { &mut *self.contents.get() }, Contents::Gone) {
+                Contents::This(t) => t,
+                _ => panic!("recursive modification of LazyTransform"),
+            };
+
+            let u = f(t);
+
+            // overwrites the `Gone` marker; nothing else observed the cell while `f` ran
+            // SAFETY (mutability): single threaded, &self, no other live references
+            *unsafe { &mut *self.contents.get() } = Contents::That(u);
+
+            self.init.set(true);
+        }
+
+        // SAFETY (initialisation): self.init, so the cell holds `That(U)`
+        match unsafe { &*self.contents.get() } {
+            Contents::That(u) => u,
+            // SAFETY: unreachable once init
+            _ => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+}
+
+impl<T, U> fmt::Debug for LazyTransform<T, U>
+where
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let that = if self.init.get() {
+            // SAFETY (initialisation): self.init
+            match unsafe { &*self.contents.get() } {
+                Contents::That(u) => Some(u),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        write!(f, "LazyTransform({that:?})")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +389,43 @@ mod tests {
         assert_eq!(got, &NoCopy(3));
     }
 
+    #[test]
+    fn get_or_try_init() {
+        let l = Lazy::new();
+
+        let err = l.get_or_try_init(|| Err::<NoCopy, _>("nope"));
+        assert_eq!(err, Err("nope"));
+        assert!(l.get().is_none());
+
+        let got = l.get_or_try_init(|| Ok::<_, &str>(NoCopy(3)));
+        assert_eq!(got, Ok(&NoCopy(3)));
+
+        // already initialised: f() isn't run
+        let again = l.get_or_try_init(|| Err::<NoCopy, _>("nope"));
+        assert_eq!(again, Ok(&NoCopy(3)));
+    }
+
+    #[test]
+    fn set_get_mut_take() {
+        let mut l = Lazy::new();
+        assert!(l.get_mut().is_none());
+
+        assert_eq!(l.set(NoCopy(3)), Ok(()));
+        assert_eq!(l.set(NoCopy(4)), Err(NoCopy(4)));
+
+        if let Some(t) = l.get_mut() {
+            t.0 = 5;
+        }
+        assert_eq!(l.get(), Some(&NoCopy(5)));
+
+        assert_eq!(l.take(), Some(NoCopy(5)));
+        assert!(l.get().is_none());
+
+        // reusable after take
+        assert_eq!(l.set(NoCopy(6)), Ok(()));
+        assert_eq!(l.get(), Some(&NoCopy(6)));
+    }
+
     #[test]
     fn double_drop() {
         let l = Lazy::new();
@@ -136,4 +446,76 @@ mod tests {
             0
         });
     }
+
+    #[test]
+    fn lazy_cell_force_and_deref() {
+        let c = LazyCell::new(|| NoCopy(3));
+        assert_eq!(c.force(), &NoCopy(3));
+        // second access returns the cached value
+        assert_eq!(&*c, &NoCopy(3));
+    }
+
+    #[test]
+    fn lazy_cell_into_inner() {
+        let c = LazyCell::new(|| NoCopy(3));
+        c.force();
+        assert_eq!(c.into_inner().ok(), Some(NoCopy(3)));
+
+        let untouched: LazyCell<NoCopy, _> = LazyCell::new(|| NoCopy(3));
+        assert!(untouched.into_inner().is_err());
+    }
+
+    #[test]
+    fn lazy_cell_drops_value_once() {
+        let c = LazyCell::new(|| String::from("hi"));
+        c.force();
+        drop(c);
+    }
+
+    #[test]
+    fn lazy_transform() {
+        let l = LazyTransform::new(3);
+        let got = l.get_or_create(|seed| NoCopy(seed + 1));
+        assert_eq!(got, &NoCopy(4));
+
+        // second access returns the cached value without running f again
+        let again = l.get_or_create(|_| NoCopy(99));
+        assert_eq!(again, &NoCopy(4));
+    }
+
+    #[test]
+    fn lazy_transform_panicking_init_drops_seed_once() {
+        use std::cell::Cell as StdCell;
+        use std::rc::Rc;
+
+        struct Counter(Rc<StdCell<usize>>);
+        impl Drop for Counter {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(StdCell::new(0));
+        let l = LazyTransform::<Counter, ()>::new(Counter(Rc::clone(&drops)));
+
+        let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            l.get_or_create(|_seed| panic!("boom"));
+        }));
+        assert!(caught.is_err());
+
+        // the seed was consumed by the panicking closure exactly once; the cell holds no duplicate
+        drop(l);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lazy_transform_recursive_init() {
+        let l = LazyTransform::new(0);
+
+        l.get_or_create(|seed| {
+            l.get_or_create(|_| 0);
+            seed
+        });
+    }
 }
@@ -0,0 +1,163 @@
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+const EMPTY: u8 = 0;
+const WRITING: u8 = 1;
+const DONE: u8 = 2;
+
+/// A best-effort, allocation-free [`Lazy`](crate::Lazy) usable with neither `alloc` nor `std`.
+///
+/// This trades the at-most-once guarantee for portability, the way `regex-automata`'s
+/// `util::lazy` does: when several callers reach an uninitialised cell at once, each runs the
+/// closure, then they compare-and-swap to publish. The winner's value is kept; the losers drop
+/// their freshly computed value and read back the winner's.
+///
+/// Because `F` may run more than once, callers **must** keep it side-effect-free and idempotent.
+/// The value itself is still written exactly once, so `&T` stays stable.
+pub struct Lazy<T> {
+    // SAFETY (racing): writes are serialised through `state`'s compare-and-swap
+    t: UnsafeCell<MaybeUninit<T>>,
+    state: AtomicU8,
+}
+
+// SAFETY: the compare-and-swap picks a single writer and publishes with release/acquire
+//         ordering; `T: Send` ferries the value to readers, `T: Sync` lets `&T` be shared.
+unsafe impl<T: Send + Sync> Sync for Lazy<T> {}
+// SAFETY: sending the cell sends its contained `T`.
+unsafe impl<T: Send> Send for Lazy<T> {}
+
+impl<T> Lazy<T> {
+    pub const fn new() -> Self {
+        Self {
+            t: UnsafeCell::new(MaybeUninit::uninit()),
+            state: AtomicU8::new(EMPTY),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == DONE {
+            // SAFETY (initialisation): DONE is only stored after the value is written
+            unsafe { Some((*self.t.get()).assume_init_ref()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or_create<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        if self.state.load(Ordering::Acquire) != DONE {
+            // F may run on several racing threads at once; only one write wins
+            let t = f();
+
+            match self.state.compare_exchange(
+                EMPTY,
+                WRITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // SAFETY (initialisation): we claimed the EMPTY -> WRITING transition
+                    // SAFETY (mutability): no other caller writes while we hold WRITING
+                    unsafe {
+                        (*self.t.get()).write(t);
+                    }
+                    self.state.store(DONE, Ordering::Release);
+                }
+                Err(_) => {
+                    // a racing caller won; drop our value and wait for theirs to publish
+                    drop(t);
+                    while self.state.load(Ordering::Acquire) != DONE {
+                        core::hint::spin_loop();
+                    }
+                }
+            }
+        }
+
+        // SAFETY (initialisation): state is DONE, so the value is published
+        unsafe { (*self.t.get()).assume_init_ref() }
+    }
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Lazy<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == DONE {
+            // SAFETY (initialisation): DONE is only stored after the value is written
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { (*self.t.get()).assume_init_drop() }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Lazy<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Lazy({:?})", self.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn get_or_create() {
+        let l = Lazy::new();
+        assert!(l.get().is_none());
+
+        let got = l.get_or_create(|| 3);
+        assert_eq!(got, &3);
+        assert_eq!(l.get(), Some(&3));
+    }
+
+    #[test]
+    fn losers_drop_and_reread_winner() {
+        let l = Arc::new(Lazy::new());
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let l = Arc::clone(&l);
+                thread::spawn(move || *l.get_or_create(|| i))
+            })
+            .collect();
+
+        let winner = *l.get_or_create(|| usize::MAX);
+        for t in threads {
+            assert_eq!(t.join().unwrap(), winner);
+        }
+    }
+
+    #[test]
+    fn drops_value_once() {
+        use std::cell::Cell as StdCell;
+        use std::rc::Rc;
+
+        struct Counter(Rc<StdCell<usize>>);
+        impl Drop for Counter {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(StdCell::new(0));
+        let l = Lazy::new();
+        l.get_or_create(|| Counter(Rc::clone(&drops)));
+
+        drop(l);
+        assert_eq!(drops.get(), 1);
+    }
+}
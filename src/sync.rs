@@ -0,0 +1,172 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    sync::Once,
+};
+
+/// A thread-safe sibling of [`crate::Lazy`].
+///
+/// Unlike the crate's default type this one is `Sync`, so it suits global or shared data the
+/// way `once_cell::sync`/`std::sync::OnceLock` do. `get_or_create` may be called from multiple
+/// threads at once; the initialiser runs at most once and concurrent callers block until it
+/// completes, then observe the value through an acquire load.
+///
+/// Built on [`std::sync::Once`] plus an `UnsafeCell<MaybeUninit<T>>`.
+pub struct Lazy<T> {
+    // SAFETY (racing): all access to `t` is ordered by `once`
+    t: UnsafeCell<MaybeUninit<T>>,
+    once: Once,
+}
+
+// SAFETY: `once` serialises the single write, and `T: Send` lets the value cross to whichever
+//         thread reads it; `T: Sync` lets the shared `&T` be handed to several threads at once.
+unsafe impl<T: Send + Sync> Sync for Lazy<T> {}
+// SAFETY: sending the cell sends its contained `T`.
+unsafe impl<T: Send> Send for Lazy<T> {}
+
+impl<T> Lazy<T> {
+    pub const fn new() -> Self {
+        Self {
+            t: UnsafeCell::new(MaybeUninit::uninit()),
+            once: Once::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> Option<T> {
+        if self.once.is_completed() {
+            // SAFETY (initialisation): `once` completed, so the value is written
+            // SAFETY (mutability): we own self
+            unsafe { Some((*self.t.get()).assume_init_read()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            // SAFETY (initialisation): `once` completed with an acquire load
+            unsafe { Some((*self.t.get()).assume_init_ref()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or_create<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        self.once.call_once(|| {
+            let t = f();
+            // SAFETY (initialisation): `call_once` runs this body exactly once
+            // SAFETY (mutability): no other thread can touch `t` until `once` publishes it
+            unsafe {
+                (*self.t.get()).write(t);
+            }
+        });
+
+        // SAFETY (initialisation): `call_once` returned, so the value is published
+        unsafe { (*self.t.get()).assume_init_ref() }
+    }
+}
+
+impl<T> fmt::Debug for Lazy<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Lazy({:?})", self.get())
+    }
+}
+
+impl<T: Clone> Clone for Lazy<T> {
+    fn clone(&self) -> Self {
+        let new = Self::new();
+        if let Some(t) = self.get() {
+            new.get_or_create(|| t.clone());
+        }
+        new
+    }
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Lazy<T> {
+    fn drop(&mut self) {
+        if self.once.is_completed() {
+            // SAFETY (initialisation): `once` completed, so the value is written
+            // SAFETY (mutability): &mut self is exclusive
+            unsafe { (*self.t.get()).assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn get_or_create() {
+        let l = Lazy::new();
+        assert!(l.get().is_none());
+
+        let got = l.get_or_create(|| 3);
+        assert_eq!(got, &3);
+        assert_eq!(l.get(), Some(&3));
+    }
+
+    #[test]
+    fn runs_at_most_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let l = Arc::new(Lazy::new());
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let l = Arc::clone(&l);
+                let calls = Arc::clone(&calls);
+                thread::spawn(move || {
+                    *l.get_or_create(|| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        i
+                    })
+                })
+            })
+            .collect();
+
+        let winner = *l.get_or_create(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            usize::MAX
+        });
+        for t in threads {
+            assert_eq!(t.join().unwrap(), winner);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn drops_value_once() {
+        use std::cell::Cell as StdCell;
+        use std::rc::Rc;
+
+        struct Counter(Rc<StdCell<usize>>);
+        impl Drop for Counter {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(StdCell::new(0));
+        let l = Lazy::new();
+        l.get_or_create(|| Counter(Rc::clone(&drops)));
+
+        drop(l);
+        assert_eq!(drops.get(), 1);
+    }
+}